@@ -1,10 +1,17 @@
-use std::cmp::min;
+use std::cmp::{min, Reverse};
+use std::collections::{BinaryHeap, HashMap};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex, MutexGuard, RwLock};
 
-use crate::concurrency::utils::lock_mutex_in_array;
 use crate::concurrency::TxIndex;
 
+/// Transactions further than this many indexes ahead of a conflicting predecessor are never
+/// tracked by the dependency graph; the graph stays bounded and those pairs just fall back to
+/// ordinary Block-STM scheduling.
+const DEFAULT_LOOK_AHEAD_WINDOW: usize = 2048;
+
 #[cfg(test)]
 #[path = "scheduler_test.rs"]
 pub mod test;
@@ -30,32 +37,72 @@ impl<'a> TransactionCommitter<'a> {
             return None;
         };
         assert!(
-            *self.commit_index_guard < self.scheduler.chunk_size,
+            *self.commit_index_guard < self.scheduler.chunk_size(),
             "The commit index must be less than the chunk size, since the scheduler is not done."
         );
+        let tx_index = *self.commit_index_guard;
 
-        // get the tx status for the tx index in commit_index_guard
-        let mut status = self.scheduler.lock_tx_status(*self.commit_index_guard);
-
-        // To schedule the tx for the commitment phase, its current status must be TransactionStatus::Executed.
-        if *status != TransactionStatus::Executed {
+        // To schedule the tx for the commitment phase, its current status must be
+        // TransactionStatus::Executed. This is a pure predicate check, so it's done with a shared
+        // read lock before taking the exclusive lock needed for the actual transition.
+        if self.scheduler.read_tx_status(tx_index) != TransactionStatus::Executed {
             return None;
         }
 
+        if let Some(filter) = &self.scheduler.pre_commit_filter {
+            match filter(tx_index) {
+                CommitDecision::Commit => {}
+                CommitDecision::Skip(reason) => {
+                    let flagged = self.scheduler.update_tx_status(tx_index, |status| {
+                        if *status == TransactionStatus::Executed {
+                            *status = TransactionStatus::Invalid;
+                            true
+                        } else {
+                            false
+                        }
+                    });
+                    return if flagged { self.skip_commit(reason) } else { None };
+                }
+                CommitDecision::Stop => {
+                    // The current transaction never commits; only the prefix committed so far
+                    // becomes the block, so the scheduler is halted directly rather than through
+                    // `halt_scheduler`, which assumes the final committed index must be excluded.
+                    self.scheduler.halt();
+                    return None;
+                }
+            }
+        }
+
         // convert the tx status to ready to be committed
-        *status = TransactionStatus::Committed;
-        
+        let committed = self.scheduler.update_tx_status(tx_index, |status| {
+            if *status != TransactionStatus::Executed {
+                return false;
+            }
+            *status = TransactionStatus::Committed;
+            true
+        });
+        if !committed {
+            return None;
+        }
+
+        if let Some(dependency_graph) = &self.scheduler.dependency_graph {
+            dependency_graph.lock().on_commit(tx_index);
+        }
+
         // increment the next tx index to be committed
         *self.commit_index_guard += 1;
 
-        // mark scheduler as done if the index of next tx to be committed is equal to the chunk size (meaning
-        // we no longer have anymore tx in the chunk)
-        if *self.commit_index_guard == self.scheduler.chunk_size {
+        // mark scheduler as done if the stream is closed and the index of the next tx to be
+        // committed is equal to the chunk size (meaning there's no more tx left to commit).
+        if *self.commit_index_guard == self.scheduler.chunk_size()
+            && self.scheduler.stream_closed.load(Ordering::Acquire)
+        {
             self.scheduler.done_marker.store(true, Ordering::Release);
         }
+        self.scheduler.notify_work();
 
         // return the tx index of the tx whose status we've set to TransactionStatus::Committed
-        Some(*self.commit_index_guard - 1)
+        Some(tx_index)
     }
 
     /// Halts the scheduler. Decrements the commit index to indicate that the final transaction to
@@ -66,9 +113,66 @@ impl<'a> TransactionCommitter<'a> {
 
         self.scheduler.halt();
     }
+
+    /// Drops the transaction at the commit index instead of committing it: advances past it
+    /// without flipping it to `Committed`, records `reason` in the scheduler's skipped list, and
+    /// re-validates higher transactions against the state without this transaction's writes.
+    /// Returns the skipped transaction's index, or `None` if it is not yet flagged `Invalid`.
+    pub fn skip_commit(&mut self, reason: impl Into<String>) -> Option<usize> {
+        if self.scheduler.done() {
+            return None;
+        };
+        assert!(
+            *self.commit_index_guard < self.scheduler.chunk_size(),
+            "The commit index must be less than the chunk size, since the scheduler is not done."
+        );
+
+        if self.scheduler.read_tx_status(*self.commit_index_guard) != TransactionStatus::Invalid {
+            return None;
+        }
+
+        let skipped_index = *self.commit_index_guard;
+        self.scheduler.skipped.lock().push((skipped_index, reason.into()));
+
+        if let Some(dependency_graph) = &self.scheduler.dependency_graph {
+            // A skip resolves `skipped_index` from the graph's perspective just as a commit does:
+            // its successors must not keep waiting on writes that will never land.
+            dependency_graph.lock().on_commit(skipped_index);
+        }
+
+        *self.commit_index_guard += 1;
+        if *self.commit_index_guard == self.scheduler.chunk_size()
+            && self.scheduler.stream_closed.load(Ordering::Acquire)
+        {
+            self.scheduler.done_marker.store(true, Ordering::Release);
+        }
+
+        // The skipped transaction's writes never land, so every higher transaction that may have
+        // read or validated against them must be re-validated.
+        self.scheduler.decrease_validation_index(skipped_index + 1);
+
+        Some(skipped_index)
+    }
 }
 
-#[derive(Debug, Default)]
+/// Outcome of a [`PreCommitFilter`] consulted by `TransactionCommitter::try_commit` right before a
+/// transaction would be committed.
+pub enum CommitDecision {
+    /// Commit the transaction as usual.
+    Commit,
+    /// Drop the transaction (see `TransactionCommitter::skip_commit`), recording `reason`, and
+    /// keep committing the rest of the chunk.
+    Skip(String),
+    /// Stop committing: the block is cut at the already-committed prefix.
+    Stop,
+}
+
+/// A caller-supplied cumulative resource budget (e.g. L1 gas, Cairo steps, calldata bytes) applied
+/// at the top of the committer's single-threaded critical section, where that cumulative state is
+/// authoritative.
+pub type PreCommitFilter = dyn Fn(TxIndex) -> CommitDecision + Send + Sync;
+
+#[derive(Default)]
 pub struct Scheduler {
     // The index of the next transaction to execute.
     execution_index: AtomicUsize,
@@ -76,13 +180,54 @@ pub struct Scheduler {
     validation_index: AtomicUsize,
     // The index of the next transaction to commit.
     commit_index: Mutex<usize>,
-    
-    chunk_size: usize,
-    // TODO(Avi, 15/05/2024): Consider using RwLock instead of Mutex.
-    tx_statuses: Box<[Mutex<TransactionStatus>]>,
+
+    // The number of transactions currently known to the scheduler. Grows via `Scheduler::extend`
+    // in streaming mode, so it's an atomic rather than a fixed constructor parameter.
+    chunk_size: AtomicUsize,
+    // Guarded by an outer lock only so `Scheduler::extend` can swap in a bigger boxed slice;
+    // per-transaction reads and writes take just the matching inner `RwLock`.
+    tx_statuses: RwLock<Box<[RwLock<TransactionStatus>]>>,
     // Set to true when all transactions have been committed, or when calling the halt_scheduler
     // procedure, providing a cheap way for all threads to exit their main loops.
     done_marker: AtomicBool,
+    // False while more transactions may still be appended via `Scheduler::extend`. While false,
+    // reaching `commit_index == chunk_size` does not mark the scheduler done, since more work may
+    // still stream in; `Scheduler::close_stream` flips this once the caller knows it won't.
+    stream_closed: AtomicBool,
+    // Caps how far `execution_index` may run ahead of `commit_index`, bounding the memory held by
+    // uncommitted-but-scheduled transactions when the chunk is an open-ended stream.
+    max_in_flight: Option<usize>,
+    // When present, gates execution of a transaction on its conflicting predecessors having
+    // committed, instead of handing out transactions in pure index order.
+    dependency_graph: Option<Mutex<DependencyGraph>>,
+    // Transactions flagged `Invalid` and then skipped via `TransactionCommitter::skip_commit`,
+    // together with the reason they were dropped, in commit order.
+    skipped: Mutex<Vec<(TxIndex, String)>>,
+    // Bumped and broadcast on `work_available` every time new schedulable work appears, so
+    // parked workers wake up instead of busy-polling `next_task` while the frontier is empty.
+    work_generation: Mutex<u64>,
+    work_available: Condvar,
+    // Consulted by `TransactionCommitter::try_commit` before committing each transaction; lets a
+    // sequencer cut the block off once a cumulative resource budget would be exceeded.
+    pre_commit_filter: Option<Box<PreCommitFilter>>,
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("execution_index", &self.execution_index)
+            .field("validation_index", &self.validation_index)
+            .field("commit_index", &self.commit_index)
+            .field("chunk_size", &self.chunk_size)
+            .field("tx_statuses", &self.tx_statuses)
+            .field("done_marker", &self.done_marker)
+            .field("stream_closed", &self.stream_closed)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("dependency_graph", &self.dependency_graph)
+            .field("skipped", &self.skipped)
+            .field("has_pre_commit_filter", &self.pre_commit_filter.is_some())
+            .finish()
+    }
 }
 
 impl Scheduler {
@@ -91,11 +236,87 @@ impl Scheduler {
             execution_index: AtomicUsize::new(0),
             validation_index: AtomicUsize::new(chunk_size),
             commit_index: Mutex::new(0),
-            chunk_size,
-            tx_statuses: std::iter::repeat_with(|| Mutex::new(TransactionStatus::ReadyToExecute))
-                .take(chunk_size)
-                .collect(),
+            chunk_size: AtomicUsize::new(chunk_size),
+            tx_statuses: RwLock::new(Self::new_tx_statuses(chunk_size)),
             done_marker: AtomicBool::new(false),
+            stream_closed: AtomicBool::new(true),
+            max_in_flight: None,
+            dependency_graph: None,
+            skipped: Mutex::new(Vec::new()),
+            work_generation: Mutex::new(0),
+            work_available: Condvar::new(),
+            pre_commit_filter: None,
+        }
+    }
+
+    /// Same as [`Self::new`], but keeps the chunk open for [`Self::extend`] instead of marking the
+    /// scheduler done once `initial_chunk_size` transactions have committed: a sequencer can feed
+    /// an open-ended transaction stream through one long-lived scheduler instead of spinning up a
+    /// fresh one per chunk. `max_in_flight` bounds how far ahead of the commit index transactions
+    /// may be scheduled for execution, so the uncommitted window stays bounded in memory.
+    pub fn new_streaming(initial_chunk_size: usize, max_in_flight: usize) -> Scheduler {
+        Scheduler {
+            stream_closed: AtomicBool::new(false),
+            max_in_flight: Some(max_in_flight),
+            ..Self::new(initial_chunk_size)
+        }
+    }
+
+    fn new_tx_statuses(len: usize) -> Box<[RwLock<TransactionStatus>]> {
+        std::iter::repeat_with(|| RwLock::new(TransactionStatus::ReadyToExecute)).take(len).collect()
+    }
+
+    /// Grows the chunk by `additional` transactions, appended past the current `chunk_size`, so
+    /// they become schedulable without resetting the execution/validation indexes or losing the
+    /// warm pipeline. Only meaningful on a [`Self::new_streaming`] scheduler.
+    pub fn extend(&self, additional: usize) {
+        if additional == 0 {
+            return;
+        }
+        let mut tx_statuses = self.tx_statuses.write();
+        let mut statuses_vec = std::mem::take(&mut *tx_statuses).into_vec();
+        statuses_vec.extend(
+            std::iter::repeat_with(|| RwLock::new(TransactionStatus::ReadyToExecute)).take(additional),
+        );
+        *tx_statuses = statuses_vec.into_boxed_slice();
+        drop(tx_statuses);
+
+        self.chunk_size.fetch_add(additional, Ordering::SeqCst);
+        self.notify_work();
+    }
+
+    /// Marks the stream closed: once every transaction known so far has committed, the scheduler
+    /// becomes [`Self::done`]. Call once the caller knows no further [`Self::extend`] is coming.
+    pub fn close_stream(&self) {
+        self.stream_closed.store(true, Ordering::Release);
+        if *self.commit_index.lock() == self.chunk_size() {
+            self.done_marker.store(true, Ordering::Release);
+        }
+        self.notify_work();
+    }
+
+    fn chunk_size(&self) -> usize {
+        self.chunk_size.load(Ordering::Acquire)
+    }
+
+    /// Same as [`Self::new`], but gives the committer a resource-budget cutoff: before committing
+    /// each transaction, `filter` is called with its index and may divert it to
+    /// [`CommitDecision::Skip`] or cut the block short with [`CommitDecision::Stop`].
+    pub fn new_with_pre_commit_filter(
+        chunk_size: usize,
+        filter: Box<PreCommitFilter>,
+    ) -> Scheduler {
+        Scheduler { pre_commit_filter: Some(filter), ..Self::new(chunk_size) }
+    }
+
+    /// Same as [`Self::new`], but additionally tracks write-write / read-write conflicts between
+    /// transactions (reported via [`Self::finish_execution_with_conflicts`]) in a bounded
+    /// dependency graph, so a transaction is never handed out for speculative execution while a
+    /// tracked, not-yet-committed predecessor could still invalidate it.
+    pub fn new_with_dependency_graph(chunk_size: usize) -> Scheduler {
+        Scheduler {
+            dependency_graph: Some(Mutex::new(DependencyGraph::new(DEFAULT_LOOK_AHEAD_WINDOW))),
+            ..Self::new(chunk_size)
         }
     }
 
@@ -115,9 +336,13 @@ impl Scheduler {
 
         let index_to_validate = self.validation_index.load(Ordering::Acquire);
         let index_to_execute = self.execution_index.load(Ordering::Acquire);
-
-        if min(index_to_validate, index_to_execute) >= self.chunk_size {
-            return Task::NoTaskAvailable;
+        let generation = self.work_generation();
+        if min(
+            self.validation_index.load(Ordering::Acquire),
+            self.execution_index.load(Ordering::Acquire),
+        ) >= self.chunk_size()
+        {
+            return Task::Park(generation);
         }
 
         // make sure to finish validation task for earlier tx first before performing any execution task
@@ -131,7 +356,41 @@ impl Scheduler {
             return Task::ExecutionTask(tx_index);
         }
 
-        Task::AskForTask
+        Task::Park(generation)
+    }
+
+    /// The current value of the work-availability generation counter, for [`Task::Park`] to carry
+    /// alongside its "no work right now" verdict so a subsequent [`Self::wait_for_work`] call can
+    /// compare against the state as of that verdict, rather than resampling on entry.
+    fn work_generation(&self) -> u64 {
+        *self.work_generation.lock()
+    }
+
+    /// Blocks the calling thread until new schedulable work appears (or `timeout` elapses, or the
+    /// scheduler finishes), to replace a busy `next_task` spin while `Task::Park` is returned.
+    /// `since_generation` must be the generation carried by the `Task::Park(since_generation)` that
+    /// prompted this call: comparing against it, rather than against the generation sampled fresh
+    /// on entry, closes the race between `next_task`'s "no work" verdict and this call — a
+    /// `notify_work` that fires in that gap must still wake (or skip) this wait, not get folded
+    /// into a new baseline and missed for the full `timeout`.
+    pub fn wait_for_work(&self, since_generation: u64, timeout: Duration) {
+        let mut generation = self.work_generation.lock();
+        let deadline = Instant::now() + timeout;
+        while *generation == since_generation && !self.done() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return;
+            }
+            self.work_available.wait_for(&mut generation, remaining);
+        }
+    }
+
+    /// Wakes every thread parked in `wait_for_work`. Called whenever a state transition creates
+    /// new schedulable work: a transaction becomes executed or ready, the validation index is
+    /// lowered, or a transaction commits and frees its successors.
+    fn notify_work(&self) {
+        *self.work_generation.lock() += 1;
+        self.work_available.notify_all();
     }
 
     /// Updates the Scheduler that an execution task has been finished and triggers the creation of
@@ -142,13 +401,52 @@ impl Scheduler {
         self.decrease_validation_index(tx_index);
     }
 
+    /// Same as [`Self::finish_execution`], but additionally registers the conflicting, earlier
+    /// transactions whose writes `tx_index` read from or overwrote, so the dependency graph (if
+    /// enabled via [`Self::new_with_dependency_graph`]) withholds `tx_index` from re-execution
+    /// until those predecessors commit. A no-op on the conflicts when the dependency graph is disabled.
+    pub fn finish_execution_with_conflicts(
+        &self,
+        tx_index: TxIndex,
+        conflicting_predecessors: impl IntoIterator<Item = TxIndex>,
+    ) {
+        self.finish_execution(tx_index);
+        if let Some(dependency_graph) = &self.dependency_graph {
+            let mut dependency_graph = dependency_graph.lock();
+            for predecessor in conflicting_predecessors {
+                dependency_graph.add_conflict(predecessor, tx_index);
+            }
+        }
+    }
+
     pub fn try_validation_abort(&self, tx_index: TxIndex) -> bool {
-        let mut status = self.lock_tx_status(tx_index);
-        if *status == TransactionStatus::Executed {
-            *status = TransactionStatus::Aborting;
-            return true;
+        if self.read_tx_status(tx_index) != TransactionStatus::Executed {
+            return false;
+        }
+        self.update_tx_status(tx_index, |status| {
+            if *status == TransactionStatus::Executed {
+                *status = TransactionStatus::Aborting;
+                return true;
+            }
+            false
+        })
+    }
+
+    /// Flags an executed transaction as permanently invalid (e.g. bad nonce, unresolvable
+    /// declare, insufficient balance), independent of scheduling order. Once the commit index
+    /// reaches it, `TransactionCommitter::skip_commit` drops it from the block instead of
+    /// committing it. Returns `false` if the transaction is not currently `Executed`.
+    pub fn try_flag_invalid(&self, tx_index: TxIndex) -> bool {
+        if self.read_tx_status(tx_index) != TransactionStatus::Executed {
+            return false;
         }
-        false
+        self.update_tx_status(tx_index, |status| {
+            if *status == TransactionStatus::Executed {
+                *status = TransactionStatus::Invalid;
+                return true;
+            }
+            false
+        })
     }
 
     /// Updates the Scheduler that a validation task has aborted and triggers the creation of new
@@ -157,7 +455,11 @@ impl Scheduler {
     pub fn finish_abort(&self, tx_index: TxIndex) -> Task {
         // set the tx to ReadyToExecute
         self.set_ready_status(tx_index);
-        if self.execution_index.load(Ordering::Acquire) > tx_index && self.try_incarnate(tx_index) {
+        self.notify_work();
+        if self.execution_index.load(Ordering::Acquire) > tx_index
+            && self.is_ready_per_dependency_graph(tx_index)
+            && self.try_incarnate(tx_index)
+        {
             Task::ExecutionTask(tx_index)
         } else {
             Task::AskForTask
@@ -174,95 +476,161 @@ impl Scheduler {
     /// Tries to takes the lock on the commit index. Returns a `TransactionCommitter` if successful,
     /// or None if the lock is already taken.
     pub fn try_enter_commit_phase(&self) -> Option<TransactionCommitter<'_>> {
-        match self.commit_index.try_lock() {
-            // return the `TransactionCommitter` with the index of the transaction to commit
-            Ok(guard) => Some(TransactionCommitter::new(self, guard)),
-            Err(TryLockError::WouldBlock) => None,
-            Err(TryLockError::Poisoned(error)) => {
-                panic!("Commit index is poisoned. Data: {:?}.", *error.get_ref())
-            }
-        }
+        // `parking_lot::Mutex` cannot be poisoned, so unlike `std::sync::Mutex` there is no
+        // poisoned case to handle here.
+        self.commit_index.try_lock().map(|guard| TransactionCommitter::new(self, guard))
     }
 
     pub fn get_n_committed_txs(&self) -> usize {
-        *self.commit_index.lock().unwrap()
+        *self.commit_index.lock()
+    }
+
+    /// Returns the transactions dropped via `TransactionCommitter::skip_commit`, together with
+    /// their skip reason, in commit order. The caller should build the block body from the
+    /// committed transactions minus these.
+    pub fn get_skipped_txs(&self) -> Vec<(TxIndex, String)> {
+        self.skipped.lock().clone()
     }
 
     pub fn halt(&self) {
         self.done_marker.store(true, Ordering::Release);
+        self.notify_work();
+    }
+
+    /// Reads a transaction's status, for pure predicate checks. Returns an owned value (rather
+    /// than a guard) since `tx_statuses` may grow concurrently via [`Self::extend`]; nothing in
+    /// the scheduler needs to hold a status lock across other operations.
+    fn read_tx_status(&self, tx_index: TxIndex) -> TransactionStatus {
+        *self.tx_statuses.read()[tx_index].read()
     }
 
-    // basically get the tx status for the given tx_index
-    fn lock_tx_status(&self, tx_index: TxIndex) -> MutexGuard<'_, TransactionStatus> {
-        lock_mutex_in_array(&self.tx_statuses, tx_index)
+    /// Applies `f` to a transaction's status under an exclusive lock, for an actual state
+    /// transition, and returns whatever `f` returns.
+    fn update_tx_status<R>(&self, tx_index: TxIndex, f: impl FnOnce(&mut TransactionStatus) -> R) -> R {
+        f(&mut self.tx_statuses.read()[tx_index].write())
     }
 
     fn set_executed_status(&self, tx_index: TxIndex) {
-        let mut status = self.lock_tx_status(tx_index);
-        assert_eq!(
-            *status,
-            TransactionStatus::Executing,
-            "Only executing transactions can gain status executed. Transaction {tx_index} is not \
-             executing. Transaction status: {status:?}."
-        );
-        *status = TransactionStatus::Executed;
+        self.update_tx_status(tx_index, |status| {
+            assert_eq!(
+                *status,
+                TransactionStatus::Executing,
+                "Only executing transactions can gain status executed. Transaction {tx_index} \
+                 is not executing. Transaction status: {status:?}."
+            );
+            *status = TransactionStatus::Executed;
+        });
     }
 
     fn set_ready_status(&self, tx_index: TxIndex) {
-        let mut status = self.lock_tx_status(tx_index);
-        assert_eq!(
-            *status,
-            TransactionStatus::Aborting,
-            "Only aborting transactions can be re-executed. Transaction {tx_index} is not \
-             aborting. Transaction status: {status:?}."
-        );
-        *status = TransactionStatus::ReadyToExecute;
+        self.update_tx_status(tx_index, |status| {
+            assert_eq!(
+                *status,
+                TransactionStatus::Aborting,
+                "Only aborting transactions can be re-executed. Transaction {tx_index} is not \
+                 aborting. Transaction status: {status:?}."
+            );
+            *status = TransactionStatus::ReadyToExecute;
+        });
     }
 
     fn decrease_validation_index(&self, target_index: TxIndex) {
         self.validation_index.fetch_min(target_index, Ordering::SeqCst);
+        self.notify_work();
     }
 
     /// Updates a transaction's status to `Executing` if it is ready to execute.
     fn try_incarnate(&self, tx_index: TxIndex) -> bool {
-        if tx_index < self.chunk_size {
-            let mut status = self.lock_tx_status(tx_index);
+        if tx_index >= self.chunk_size() || self.read_tx_status(tx_index) != TransactionStatus::ReadyToExecute {
+            return false;
+        }
+        self.update_tx_status(tx_index, |status| {
             if *status == TransactionStatus::ReadyToExecute {
                 *status = TransactionStatus::Executing;
                 return true;
             }
-        }
-        false
+            false
+        })
     }
 
     fn next_version_to_validate(&self) -> Option<TxIndex> {
+        let chunk_size = self.chunk_size();
         let index_to_validate = self.validation_index.load(Ordering::Acquire);
-        if index_to_validate >= self.chunk_size {
+        if index_to_validate >= chunk_size {
             return None;
         }
         let index_to_validate = self.validation_index.fetch_add(1, Ordering::SeqCst);
-        if index_to_validate < self.chunk_size {
-            let status = self.lock_tx_status(index_to_validate);
-            if *status == TransactionStatus::Executed {
-                return Some(index_to_validate);
-            }
+        if index_to_validate < chunk_size
+            && self.read_tx_status(index_to_validate) == TransactionStatus::Executed
+        {
+            return Some(index_to_validate);
         }
         None
     }
 
     /// Returns the next transaction index to be executed and increment the next transaction index by 1.
     fn next_version_to_execute(&self) -> Option<TxIndex> {
+        // Transactions that were blocked on a conflicting predecessor are re-surfaced here once
+        // that predecessor commits, since the sequential cursor below has already moved past
+        // them and won't revisit them on its own.
+        if let Some(dependency_graph) = &self.dependency_graph {
+            let freed = dependency_graph.lock().pop_ready();
+            if let Some(tx_index) = freed {
+                // This index already bypasses `execution_index`'s sequential count, so it must
+                // still respect the same in-flight cap the cursor path below enforces - otherwise
+                // a dependency-graph-freed transaction would bypass `max_in_flight` entirely. If
+                // the budget isn't available right now, put it back on the frontier instead of
+                // dropping it: like the sequential cursor, nothing else will ever re-surface it.
+                if !self.in_flight_budget_available(tx_index) {
+                    dependency_graph.lock().push_ready(tx_index);
+                } else if self.try_incarnate(tx_index) {
+                    return Some(tx_index);
+                }
+            }
+        }
+
+        let chunk_size = self.chunk_size();
         let index_to_execute = self.execution_index.load(Ordering::Acquire);
-        if index_to_execute >= self.chunk_size {
+        if index_to_execute >= chunk_size || !self.in_flight_budget_available(index_to_execute) {
             return None;
         }
         let index_to_execute = self.execution_index.fetch_add(1, Ordering::SeqCst);
+        if index_to_execute >= chunk_size {
+            return None;
+        }
+        if !self.is_ready_per_dependency_graph(index_to_execute) {
+            return None;
+        }
         if self.try_incarnate(index_to_execute) {
             return Some(index_to_execute);
         }
         None
     }
 
+    /// Returns `false` only when [`Self::max_in_flight`] is set and `index_to_execute` would push
+    /// the window of uncommitted-but-scheduled transactions past that cap. Uses a non-blocking
+    /// `try_lock` on the commit index so a contended committer never stalls execution; on
+    /// contention we optimistically allow the task, since the cap is a soft memory-growth bound
+    /// rather than a correctness requirement.
+    fn in_flight_budget_available(&self, index_to_execute: TxIndex) -> bool {
+        match self.max_in_flight {
+            None => true,
+            Some(max_in_flight) => match self.commit_index.try_lock() {
+                Some(commit_index) => index_to_execute.saturating_sub(*commit_index) < max_in_flight,
+                None => true,
+            },
+        }
+    }
+
+    /// Returns `false` only when the dependency graph is enabled and `tx_index` still has an
+    /// uncommitted conflicting predecessor.
+    fn is_ready_per_dependency_graph(&self, tx_index: TxIndex) -> bool {
+        match &self.dependency_graph {
+            None => true,
+            Some(dependency_graph) => dependency_graph.lock().is_ready(tx_index),
+        }
+    }
+
     /// Returns the done marker.
     fn done(&self) -> bool {
         self.done_marker.load(Ordering::Acquire)
@@ -270,15 +638,100 @@ impl Scheduler {
 
     #[cfg(any(feature = "testing", test))]
     pub fn set_tx_status(&self, tx_index: TxIndex, status: TransactionStatus) {
-        if tx_index < self.chunk_size {
-            let mut tx_status = self.lock_tx_status(tx_index);
-            *tx_status = status;
+        if tx_index < self.chunk_size() {
+            self.update_tx_status(tx_index, |tx_status| *tx_status = status);
         }
     }
 
     #[cfg(any(feature = "testing", test))]
-    pub fn get_tx_status(&self, tx_index: TxIndex) -> MutexGuard<'_, TransactionStatus> {
-        self.lock_tx_status(tx_index)
+    pub fn get_tx_status(&self, tx_index: TxIndex) -> TransactionStatus {
+        self.read_tx_status(tx_index)
+    }
+}
+
+/// Bounded look-ahead conflict graph backing the optional dependency-aware scheduling mode.
+///
+/// Nodes are transaction indexes; a directed edge `predecessor -> successor` (`predecessor <
+/// successor`) means `successor` touched a state key that `predecessor` also touched with at
+/// least one write, so `successor` must not execute until `predecessor` commits. `frontier` holds
+/// every transaction that was once blocked but whose last outstanding predecessor has since
+/// committed, ordered so the lowest index is always handed out first.
+#[derive(Debug)]
+struct DependencyGraph {
+    successors: HashMap<TxIndex, Vec<TxIndex>>,
+    in_degree: HashMap<TxIndex, usize>,
+    frontier: BinaryHeap<Reverse<TxIndex>>,
+    // Every index below this one has already been resolved via `on_commit` (the committer only
+    // ever advances one index at a time, in order, so this is just the count of `on_commit` calls
+    // so far). A conflict edge registered against a predecessor below this line afterwards (e.g. a
+    // re-executed incarnation reporting the very predecessor whose commit triggered its abort)
+    // must not add an in-degree that will now never be decremented.
+    resolved_up_to: TxIndex,
+    look_ahead_window: usize,
+}
+
+impl DependencyGraph {
+    fn new(look_ahead_window: usize) -> Self {
+        Self {
+            successors: HashMap::new(),
+            in_degree: HashMap::new(),
+            frontier: BinaryHeap::new(),
+            resolved_up_to: 0,
+            look_ahead_window,
+        }
+    }
+
+    /// Registers that `successor` conflicts with the earlier `predecessor`. Pairs further apart
+    /// than the look-ahead window are not tracked, so `successor` just falls back to ordinary
+    /// Block-STM scheduling against that predecessor. A no-op if `predecessor` has already
+    /// committed (or been skipped): `on_commit` already ran for it and won't run again, so
+    /// recording the edge now would block `successor` forever.
+    fn add_conflict(&mut self, predecessor: TxIndex, successor: TxIndex) {
+        assert!(predecessor < successor, "Conflict edges must point from an earlier transaction.");
+        if predecessor < self.resolved_up_to || successor - predecessor > self.look_ahead_window {
+            return;
+        }
+        let successors = self.successors.entry(predecessor).or_default();
+        if successors.contains(&successor) {
+            return;
+        }
+        successors.push(successor);
+        *self.in_degree.entry(successor).or_insert(0) += 1;
+    }
+
+    /// Returns whether `tx_index` has no tracked, uncommitted predecessor left.
+    fn is_ready(&self, tx_index: TxIndex) -> bool {
+        self.in_degree.get(&tx_index).copied().unwrap_or(0) == 0
+    }
+
+    /// Notifies the graph that `committed_index` has committed (or been skipped, see
+    /// `TransactionCommitter::skip_commit`): every tracked successor loses one predecessor, and
+    /// those reaching zero become schedulable again via the frontier. Also marks `committed_index`
+    /// resolved so a conflict edge reported against it later is never recorded.
+    fn on_commit(&mut self, committed_index: TxIndex) {
+        self.resolved_up_to = self.resolved_up_to.max(committed_index + 1);
+        let Some(successors) = self.successors.remove(&committed_index) else {
+            return;
+        };
+        for successor in successors {
+            let in_degree =
+                self.in_degree.get_mut(&successor).expect("Successor must have a tracked in-degree.");
+            *in_degree -= 1;
+            if *in_degree == 0 {
+                self.frontier.push(Reverse(successor));
+            }
+        }
+    }
+
+    /// Pops the lowest-index transaction that was freed by a predecessor committing, if any.
+    fn pop_ready(&mut self) -> Option<TxIndex> {
+        self.frontier.pop().map(|Reverse(tx_index)| tx_index)
+    }
+
+    /// Puts a transaction popped via [`Self::pop_ready`] back on the frontier, e.g. because the
+    /// caller couldn't act on it yet (such as a temporarily exhausted `max_in_flight` budget).
+    fn push_ready(&mut self, tx_index: TxIndex) {
+        self.frontier.push(Reverse(tx_index));
     }
 }
 
@@ -288,6 +741,10 @@ pub enum Task {
     ValidationTask(TxIndex),
     AskForTask,
     NoTaskAvailable,
+    // No work is currently schedulable but the chunk isn't done; the worker should call
+    // `Scheduler::wait_for_work` with the carried generation instead of immediately re-polling
+    // `next_task`.
+    Park(u64),
     Done,
 }
 
@@ -298,4 +755,7 @@ pub enum TransactionStatus {
     Executed,
     Aborting,
     Committed,
+    // Flagged unrecoverably invalid via `Scheduler::try_flag_invalid`; dropped from the block by
+    // `TransactionCommitter::skip_commit` once the commit index reaches it.
+    Invalid,
 }
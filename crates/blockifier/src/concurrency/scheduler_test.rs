@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use super::*;
+
+#[test]
+fn dependency_graph_add_conflict_against_already_committed_predecessor_does_not_block() {
+    // Regression test: a predecessor that commits before its conflict edge is even registered
+    // (the common case when a transaction aborts because of that very commit, re-executes, and
+    // reports the same predecessor again) must not leave the successor blocked forever.
+    let mut graph = DependencyGraph::new(DEFAULT_LOOK_AHEAD_WINDOW);
+    graph.on_commit(0);
+    graph.add_conflict(0, 1);
+    assert!(graph.is_ready(1));
+    assert_eq!(graph.pop_ready(), None);
+}
+
+#[test]
+fn dependency_graph_add_conflict_before_commit_blocks_until_commit() {
+    let mut graph = DependencyGraph::new(DEFAULT_LOOK_AHEAD_WINDOW);
+    graph.add_conflict(0, 1);
+    assert!(!graph.is_ready(1));
+    graph.on_commit(0);
+    assert!(graph.is_ready(1));
+    assert_eq!(graph.pop_ready(), Some(1));
+    assert_eq!(graph.pop_ready(), None);
+}
+
+#[test]
+fn dependency_graph_frontier_pops_in_increasing_order() {
+    let mut graph = DependencyGraph::new(DEFAULT_LOOK_AHEAD_WINDOW);
+    graph.add_conflict(0, 5);
+    graph.add_conflict(0, 3);
+    graph.add_conflict(0, 4);
+    graph.on_commit(0);
+    assert_eq!(graph.pop_ready(), Some(3));
+    assert_eq!(graph.pop_ready(), Some(4));
+    assert_eq!(graph.pop_ready(), Some(5));
+    assert_eq!(graph.pop_ready(), None);
+}
+
+#[test]
+fn dependency_graph_requires_all_predecessors_to_commit() {
+    let mut graph = DependencyGraph::new(DEFAULT_LOOK_AHEAD_WINDOW);
+    graph.add_conflict(0, 2);
+    graph.add_conflict(1, 2);
+    graph.on_commit(0);
+    assert!(!graph.is_ready(2));
+    graph.on_commit(1);
+    assert!(graph.is_ready(2));
+    assert_eq!(graph.pop_ready(), Some(2));
+}
+
+#[test]
+fn dependency_graph_ignores_conflicts_outside_look_ahead_window() {
+    let mut graph = DependencyGraph::new(2);
+    graph.add_conflict(0, 10);
+    assert!(graph.is_ready(10));
+}
+
+#[test]
+fn skip_commit_flags_invalid_tx_and_advances_commit_index() {
+    let scheduler = Scheduler::new(3);
+    scheduler.set_tx_status(0, TransactionStatus::Invalid);
+
+    let mut committer = scheduler.try_enter_commit_phase().unwrap();
+    assert_eq!(committer.skip_commit("bad nonce"), Some(0));
+    assert_eq!(scheduler.get_n_committed_txs(), 1);
+    assert_eq!(scheduler.get_skipped_txs(), vec![(0, "bad nonce".to_string())]);
+}
+
+#[test]
+fn skip_commit_resolves_dependency_graph_successors() {
+    // Regression test: a skipped transaction must resolve the dependency graph the same way a
+    // commit does, or a successor that conflicted with it would never become ready again.
+    let scheduler = Scheduler::new_with_dependency_graph(3);
+    scheduler.set_tx_status(1, TransactionStatus::Executing);
+    scheduler.finish_execution_with_conflicts(1, std::iter::once(0));
+    scheduler.set_tx_status(0, TransactionStatus::Invalid);
+
+    let mut committer = scheduler.try_enter_commit_phase().unwrap();
+    assert_eq!(committer.skip_commit("bad nonce"), Some(0));
+    drop(committer);
+
+    // tx 1 conflicted with tx 0; once tx 0 is skipped, tx 1 must be schedulable again.
+    scheduler.set_tx_status(1, TransactionStatus::ReadyToExecute);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(1));
+}
+
+#[test]
+fn wait_for_work_does_not_block_when_generation_already_advanced() {
+    // Regression test: if `notify_work` fires in the gap between `next_task`'s "no work" verdict
+    // and the `wait_for_work` call, the wait must return immediately rather than blocking for the
+    // full timeout.
+    let scheduler = Scheduler::new(1);
+    // Simulate tx 0 already being worked on elsewhere, so this `next_task` call finds no work.
+    scheduler.set_tx_status(0, TransactionStatus::Executing);
+    let Task::Park(generation) = scheduler.next_task() else {
+        panic!("Expected Task::Park since tx 0 cannot be incarnated while Executing.");
+    };
+
+    scheduler.set_tx_status(0, TransactionStatus::Aborting);
+    scheduler.finish_abort(0);
+
+    let start = std::time::Instant::now();
+    scheduler.wait_for_work(generation, Duration::from_secs(10));
+    assert!(start.elapsed() < Duration::from_secs(1), "wait_for_work blocked despite a prior notify");
+}
+
+#[test]
+fn extend_grows_chunk_size_and_schedules_new_transactions() {
+    let scheduler = Scheduler::new_streaming(1, 10);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(0));
+    assert!(matches!(scheduler.next_task(), Task::Park(_)));
+
+    scheduler.extend(1);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(1));
+}
+
+#[test]
+fn close_stream_marks_done_once_all_known_transactions_commit() {
+    let scheduler = Scheduler::new_streaming(1, 10);
+    scheduler.set_tx_status(0, TransactionStatus::Executed);
+    let mut committer = scheduler.try_enter_commit_phase().unwrap();
+    assert_eq!(committer.try_commit(), Some(0));
+    drop(committer);
+
+    assert_ne!(scheduler.next_task(), Task::Done);
+    scheduler.close_stream();
+    assert_eq!(scheduler.next_task(), Task::Done);
+}
+
+#[test]
+fn max_in_flight_caps_the_execution_window_ahead_of_commit_index() {
+    let scheduler = Scheduler::new_streaming(4, 2);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(0));
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(1));
+    // Two transactions are already in flight (scheduled, not yet committed); a third must not be
+    // handed out until one of them commits.
+    assert!(matches!(scheduler.next_task(), Task::Park(_)));
+}
+
+#[test]
+fn dependency_graph_frontier_path_also_respects_max_in_flight() {
+    // Regression test: a transaction freed by the dependency graph's frontier must be subject to
+    // the same `max_in_flight` cap as the sequential cursor, and must not be dropped (it should
+    // stay on the frontier) if the budget is temporarily unavailable.
+    let scheduler = Scheduler { max_in_flight: Some(1), ..Scheduler::new_with_dependency_graph(3) };
+    // Simulate tx 2 already freed by the dependency graph (its predecessor committed) while tx 0
+    // is still in flight (scheduled but not committed), exhausting the budget of 1.
+    scheduler.execution_index.store(1, Ordering::SeqCst);
+    scheduler.dependency_graph.as_ref().unwrap().lock().push_ready(2);
+
+    assert_eq!(scheduler.next_version_to_execute(), None);
+    assert_eq!(scheduler.dependency_graph.as_ref().unwrap().lock().pop_ready(), Some(2));
+}